@@ -0,0 +1,46 @@
+//! A wrapper which counts the number of records passed through it
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+use dataflow::channels::Content;
+use timely_communication::Push;
+
+use super::{Mutates, into_owned};
+
+/// A pusher that accumulates a running count of the records it forwards, in addition to
+/// forwarding them on to `pusher` unchanged (aside from the ownership it needs to take).
+pub struct Counter<T, D, P: Push<(T, Content<D>)>> {
+    pusher: P,
+    count: Rc<RefCell<i64>>,
+    phantom: PhantomData<(T, D)>,
+}
+
+impl<T, D, P: Push<(T, Content<D>)>> Counter<T, D, P> {
+    /// Creates a new `Counter`, wrapping `pusher` and accumulating into the shared `count`.
+    pub fn new(pusher: P, count: Rc<RefCell<i64>>) -> Counter<T, D, P> {
+        Counter {
+            pusher: pusher,
+            count: count,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, D: Clone, P: Push<(T, Content<D>)>> Push<(T, Rc<Content<D>>)> for Counter<T, D, P> {
+    #[inline]
+    fn push(&mut self, message: &mut Option<(T, Rc<Content<D>>)>) {
+        let mut to_push = message.take().map(|(time, data)| {
+            *self.count.borrow_mut() += data.len() as i64;
+            let owned = into_owned(data);
+            (time, owned)
+        });
+        self.pusher.push(&mut to_push);
+    }
+}
+
+impl<T, D, P: Push<(T, Content<D>)>> Mutates for Counter<T, D, P> {
+    // `Counter` always hands its batch on to `pusher`, which expects to own it.
+    fn mutates(&self) -> bool { true }
+}
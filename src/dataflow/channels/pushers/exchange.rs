@@ -0,0 +1,70 @@
+//! A wrapper which routes each record of a batch to one of several target pushers
+
+use std::rc::Rc;
+use std::marker::PhantomData;
+
+use Data;
+use dataflow::channels::Content;
+use timely_communication::Push;
+
+use super::Mutates;
+
+/// A pusher that routes each record of a batch to one of several target pushers, chosen by
+/// hashing the record with `hash_func`.
+pub struct Exchange<T, D, P: Push<(T, Content<D>)>, H: Fn(&D)->u64> {
+    pushers: Vec<P>,
+    buffers: Vec<Vec<D>>,
+    hash_func: H,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Clone, D: Data, P: Push<(T, Content<D>)>, H: Fn(&D)->u64> Exchange<T, D, P, H> {
+    /// Allocates a new `Exchange` pusher, fanning records out across `pushers` using `hash_func`.
+    pub fn new(pushers: Vec<P>, hash_func: H) -> Exchange<T, D, P, H> {
+        let buffers = pushers.iter().map(|_| Vec::with_capacity(Content::<D>::default_length())).collect();
+        Exchange {
+            pushers: pushers,
+            buffers: buffers,
+            hash_func: hash_func,
+            phantom: PhantomData,
+        }
+    }
+    #[inline]
+    fn flush(&mut self, index: usize, time: &T) {
+        if !self.buffers[index].is_empty() {
+            Content::push_at(&mut self.buffers[index], time.clone(), &mut self.pushers[index]);
+        }
+    }
+}
+
+impl<T: Clone, D: Data, P: Push<(T, Content<D>)>, H: Fn(&D)->u64> Push<(T, Rc<Content<D>>)> for Exchange<T, D, P, H> {
+    #[inline]
+    fn push(&mut self, message: &mut Option<(T, Rc<Content<D>>)>) {
+        if let Some((ref time, ref data)) = *message {
+            // Routing only ever needs to read each record (to hash it) and clone it into the
+            // buffer for its target worker, so we never take ownership of the shared batch.
+            let hash_func = &self.hash_func;
+            for datum in data.iter() {
+                let index = (hash_func(datum) % self.pushers.len() as u64) as usize;
+                self.buffers[index].push(datum.clone());
+                if self.buffers[index].len() == self.buffers[index].capacity() {
+                    self.flush(index, time);
+                }
+            }
+            for index in 0 .. self.pushers.len() {
+                self.flush(index, time);
+            }
+        }
+        else {
+            for pusher in self.pushers.iter_mut() {
+                pusher.push(&mut None);
+            }
+        }
+    }
+}
+
+impl<T, D, P: Push<(T, Content<D>)>, H: Fn(&D)->u64> Mutates for Exchange<T, D, P, H> {
+    // Routing reads each record to hash and clone it; it never needs to own or mutate the
+    // shared batch, so it can share one allocation with any sibling outputs of the same `Tee`.
+    fn mutates(&self) -> bool { false }
+}
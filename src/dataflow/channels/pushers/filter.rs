@@ -0,0 +1,102 @@
+//! A wrapper which retains only the values for which a predicate holds
+
+use std::rc::Rc;
+
+use dataflow::channels::Content;
+use timely_communication::{Push, PushRef};
+
+use super::{Mutates, into_owned};
+
+/// A pusher that retains only the values of each batch for which `func` evaluates to true.
+pub struct FilterPusher<F, P> {
+    func: F,
+    pusher: P
+}
+
+impl<F: Fn(&D) -> bool, T, D: Clone, P: Push<(T, Content<D>)>> Push<(T, Rc<Content<D>>)> for FilterPusher<F, P> {
+    #[inline]
+    fn push(&mut self, message: &mut Option<(T, Rc<Content<D>>)>) {
+        // Retaining compacts the batch in place, so we need to own it exclusively: take it
+        // outright if we're the sole owner of the shared `Rc`, otherwise clone it out.
+        let mut to_push = message.take().map(|(time, data)| {
+            let mut owned = into_owned(data);
+            let mut retained = owned.replace_with(Vec::new());
+            retained.retain(|datum| (self.func)(datum));
+            (time, Content::from_typed(&mut retained))
+        });
+        self.pusher.push(&mut to_push);
+    }
+}
+
+impl<F: Fn(&D) -> bool, T: Clone, D: Clone, P: Push<(T, Content<D>)>> PushRef<(T, Content<D>)> for FilterPusher<F, P> {
+    #[inline]
+    fn push_ref(&mut self, message: Option<&(T, Content<D>)>) {
+        let mut mapped = message.map(|&(ref time, ref data)| {
+            let mut retained: Vec<D> = data.iter().filter(|datum| (self.func)(datum)).cloned().collect();
+            (time.clone(), Content::from_typed(&mut retained))
+        });
+        self.pusher.push(&mut mapped);
+    }
+}
+
+impl<F, P> Mutates for FilterPusher<F, P> {
+    // Compacting a batch in place requires owning it exclusively.
+    fn mutates(&self) -> bool { true }
+}
+
+impl<F, P> FilterPusher<F, P> {
+    /// Creates a new pusher that will retain only the values for which `func` returns true before
+    /// passing the (possibly empty) batch on to `pusher`.
+    pub fn new(func: F, pusher: P) -> FilterPusher<F, P> {
+        FilterPusher {
+            func: func,
+            pusher: pusher,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use timely_communication::{Push, PushRef};
+    use dataflow::channels::Content;
+    use super::FilterPusher;
+
+    struct Collect {
+        seen: Rc<RefCell<Vec<i32>>>,
+    }
+
+    impl Push<(usize, Content<i32>)> for Collect {
+        fn push(&mut self, message: &mut Option<(usize, Content<i32>)>) {
+            if let Some((_, data)) = message.take() {
+                self.seen.borrow_mut().extend(data.iter().cloned());
+            }
+        }
+    }
+
+    #[test]
+    fn push_retains_only_matching_elements() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut pusher = FilterPusher::new(|x: &i32| x % 2 == 0, Collect { seen: seen.clone() });
+
+        let mut batch = vec![1, 2, 3, 4, 5, 6];
+        let mut message = Some((0usize, Rc::new(Content::from_typed(&mut batch))));
+        pusher.push(&mut message);
+
+        assert_eq!(*seen.borrow(), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn push_ref_retains_only_matching_elements() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut pusher = FilterPusher::new(|x: &i32| x % 2 == 0, Collect { seen: seen.clone() });
+
+        let mut batch = vec![1, 2, 3, 4, 5, 6];
+        let message = (0usize, Content::from_typed(&mut batch));
+        pusher.push_ref(Some(&message));
+
+        assert_eq!(*seen.borrow(), vec![2, 4, 6]);
+    }
+}
@@ -0,0 +1,28 @@
+//! A wrapper which calls a closure on every batch it sees, without copying it
+
+use dataflow::channels::Content;
+use timely_communication::PushRef;
+
+/// A pusher that calls `func` on each batch it observes by reference, without altering or
+/// copying it.
+pub struct InspectPusher<F> {
+    func: F,
+}
+
+impl<T, D, F: FnMut(&T, &Content<D>)> PushRef<(T, Content<D>)> for InspectPusher<F> {
+    #[inline]
+    fn push_ref(&mut self, message: Option<&(T, Content<D>)>) {
+        if let Some(&(ref time, ref data)) = message {
+            (self.func)(time, data);
+        }
+    }
+}
+
+impl<F> InspectPusher<F> {
+    /// Creates a new pusher that will call `func` on every batch that passes through it.
+    pub fn new(func: F) -> InspectPusher<F> {
+        InspectPusher {
+            func: func,
+        }
+    }
+}
@@ -3,6 +3,8 @@
 use dataflow::channels::Content;
 use timely_communication::{Push, PushRef};
 
+use super::Mutates;
+
 /// A pusher that applies a function to every incoming value
 pub struct MapPusher<F, P> {
     func: F,
@@ -25,6 +27,13 @@ impl<F: Fn(&D1) -> D2, D1, D2, P: Push<D2>> PushRef<D1> for MapPusher<F, P> {
     }
 }
 
+impl<F, P> Mutates for MapPusher<F, P> {
+    // `func` takes each message by value (`map_batch` hands its `logic` an owned batch to consume
+    // or update in place), so `MapPusher` can never work from a shared, borrowed batch; it always
+    // needs to own the one it gets.
+    fn mutates(&self) -> bool { true }
+}
+
 impl<F, P> MapPusher<F, P> {
     /// Creates a new pusher that will apply `func` to everything before passing it to `pusher`.
     pub fn new(func: F, pusher: P) -> MapPusher<F, P> {
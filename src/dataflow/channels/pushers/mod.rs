@@ -1,10 +1,39 @@
+use std::rc::Rc;
+
+use dataflow::channels::Content;
+
 pub use self::map::MapPusher;
+pub use self::filter::FilterPusher;
+pub use self::inspect::InspectPusher;
 pub use self::tee::{Tee, TeeHelper};
 pub use self::exchange::Exchange;
 pub use self::counter::Counter;
 
 pub mod map;
+pub mod filter;
+pub mod inspect;
 pub mod tee;
 pub mod exchange;
 pub mod counter;
 pub mod buffer;
+
+/// Declares whether a `Push` implementor needs exclusive, owned access to each batch it receives.
+///
+/// `Tee` hands every registered output a cheaply-cloned handle onto one shared batch. A pusher
+/// that only reads a batch (an `Inspect`-like tap, a by-reference `Exchange`) can say `false` and
+/// keep working from the shared handle for as long as it likes, at no copying cost at all, no
+/// matter how many other outputs there are. A pusher that needs to consume or mutate its batch
+/// (`map`, `map_in_place`, `filter`) says `true`; `Tee` still has to hand those consumers an owned
+/// copy eventually, but it serves them last and, for whichever one turns out to be the sole
+/// remaining owner of the shared batch, that copy is free.
+pub trait Mutates {
+    /// Returns `true` if this pusher needs owned, exclusive access to each batch it receives.
+    fn mutates(&self) -> bool;
+}
+
+/// Takes ownership of a `Tee`-shared batch: outright, if we happen to be its sole owner, or by
+/// cloning it out otherwise. Every `Mutates`-true pusher needs this to get from the `Rc`-shared
+/// batch `Tee` hands it to the owned `Content` it actually operates on.
+pub fn into_owned<D: Clone>(data: Rc<Content<D>>) -> Content<D> {
+    Rc::try_unwrap(data).unwrap_or_else(|rc| (*rc).clone())
+}
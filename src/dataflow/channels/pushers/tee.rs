@@ -8,29 +8,52 @@ use abomonation::Abomonation;
 
 use timely_communication::{Push, PushRef};
 
+use super::Mutates;
+
 /// Wraps a shared list of `Box<Push>` to forward pushes to. Owned by `Stream`.
 pub struct Tee<T: 'static, D: 'static> {
-    buffer: Vec<D>,
     ref_outputs: Rc<RefCell<Vec<Box<PushRef<(T, Content<D>)>>>>>,
-    outputs: Rc<RefCell<Vec<Box<Push<(T, Content<D>)>>>>>,
+    outputs: Rc<RefCell<Vec<Box<TeeOutput<T, D>>>>>,
 }
 
+/// The trait object bound required of everything registered with `TeeHelper::add_pusher`.
+///
+/// Combining `Push` and `Mutates` into a single trait lets `Tee` store its outputs as one
+/// homogeneous list of trait objects while still being able to ask each of them, at push time,
+/// whether it needs exclusive access to its batch.
+pub trait TeeOutput<T, D>: Push<(T, Rc<Content<D>>)> + Mutates {}
+impl<T, D, P: Push<(T, Rc<Content<D>>)> + Mutates> TeeOutput<T, D> for P {}
+
 impl<T: Clone+'static, D: Abomonation+Clone+'static> Push<(T, Content<D>)> for Tee<T, D> {
     #[inline]
     fn push(&mut self, message: &mut Option<(T, Content<D>)>) {
         for ref_pusher in self.ref_outputs.borrow_mut().iter_mut() {
             ref_pusher.push_ref(message.as_ref());
         }
-        if let Some((ref time, ref mut data)) = *message {
+        if let Some((time, data)) = message.take() {
             let mut pushers = self.outputs.borrow_mut();
-            for index in 0..pushers.len() {
-                if index < pushers.len() - 1 {
-                    // TODO : was `push_all`, but is now `extend`, slow.
-                    self.buffer.extend_from_slice(data);
-                    Content::push_at(&mut self.buffer, (*time).clone(), &mut pushers[index]);
+            let shared = Rc::new(data);
+
+            // Every output shares one `Rc`-backed batch rather than a private copy. A read-only
+            // consumer (`mutates() == false`) can just hold onto a clone of the `Rc` and read
+            // through it, at no copying cost, no matter how many outputs there are. Serving them
+            // first means that by the time the last mutating consumer runs, no other clone of
+            // `shared` remains unless some consumer kept its own, so handing it the original `Rc`
+            // (instead of another clone) lets it discover it's the sole owner and avoid a copy too.
+            for index in 0 .. pushers.len() {
+                if !pushers[index].mutates() {
+                    let mut to_push = Some((time.clone(), Rc::clone(&shared)));
+                    pushers[index].push(&mut to_push);
                 }
-                else {
-                    Content::push_at(data, (*time).clone(), &mut pushers[index]);
+            }
+
+            let last_mutating = (0 .. pushers.len()).filter(|&index| pushers[index].mutates()).last();
+            let mut shared = Some(shared);
+            for index in 0 .. pushers.len() {
+                if pushers[index].mutates() {
+                    let batch = if Some(index) == last_mutating { shared.take().unwrap() } else { Rc::clone(shared.as_ref().unwrap()) };
+                    let mut to_push = Some((time.clone(), batch));
+                    pushers[index].push(&mut to_push);
                 }
             }
         }
@@ -48,7 +71,6 @@ impl<T, D> Tee<T, D> {
         let ref_outputs = Rc::new(RefCell::new(Vec::new()));
         let outputs = Rc::new(RefCell::new(Vec::new()));
         let port = Tee {
-            buffer: Vec::with_capacity(Content::<D>::default_length()),
             ref_outputs: ref_outputs.clone(),
             outputs: outputs.clone(),
         };
@@ -60,7 +82,6 @@ impl<T, D> Tee<T, D> {
 impl<T, D> Clone for Tee<T, D> {
     fn clone(&self) -> Tee<T, D> {
         Tee {
-            buffer: Vec::with_capacity(self.buffer.capacity()),
             ref_outputs: self.ref_outputs.clone(),
             outputs: self.outputs.clone(),
         }
@@ -71,7 +92,7 @@ impl<T, D> Clone for Tee<T, D> {
 /// A shared list of `Box<Push>` used to add `Push` implementors.
 pub struct TeeHelper<T, D> {
     ref_outputs: Rc<RefCell<Vec<Box<PushRef<(T, Content<D>)>>>>>,
-    outputs: Rc<RefCell<Vec<Box<Push<(T, Content<D>)>>>>>,
+    outputs: Rc<RefCell<Vec<Box<TeeOutput<T, D>>>>>,
 }
 
 impl<T, D> TeeHelper<T, D> {
@@ -80,7 +101,7 @@ impl<T, D> TeeHelper<T, D> {
         self.ref_outputs.borrow_mut().push(Box::new(pusher));
     }
     /// Adds a new `Push` implementor to the list of recipients shared with a `Stream`.
-    pub fn add_pusher<P: Push<(T, Content<D>)>+'static>(&self, pusher: P) {
+    pub fn add_pusher<P: Push<(T, Rc<Content<D>>)>+Mutates+'static>(&self, pusher: P) {
         self.outputs.borrow_mut().push(Box::new(pusher));
     }
 }
@@ -94,3 +115,50 @@ impl<T, D> Clone for TeeHelper<T, D> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use timely_communication::Push;
+    use dataflow::channels::Content;
+    use super::{Tee, Mutates};
+
+    struct Collect {
+        mutates: bool,
+        seen: Rc<RefCell<Vec<i32>>>,
+    }
+
+    impl Push<(usize, Rc<Content<i32>>)> for Collect {
+        fn push(&mut self, message: &mut Option<(usize, Rc<Content<i32>>)>) {
+            if let Some((_, data)) = message.take() {
+                self.seen.borrow_mut().extend(data.iter().cloned());
+            }
+        }
+    }
+
+    impl Mutates for Collect {
+        fn mutates(&self) -> bool { self.mutates }
+    }
+
+    #[test]
+    fn every_output_sees_the_full_batch_regardless_of_mutates() {
+        let (mut tee, registrar) = Tee::<usize, i32>::new();
+
+        let read_only = Rc::new(RefCell::new(Vec::new()));
+        let mutating = Rc::new(RefCell::new(Vec::new()));
+
+        registrar.add_pusher(Collect { mutates: false, seen: read_only.clone() });
+        registrar.add_pusher(Collect { mutates: true, seen: mutating.clone() });
+
+        let mut batch = vec![1, 2, 3];
+        let mut message = Some((0usize, Content::from_typed(&mut batch)));
+        tee.push(&mut message);
+
+        // Both outputs must see the whole batch, whether they were served from the shared `Rc`
+        // (read-only) or handed the original `Rc` last (mutating).
+        assert_eq!(*read_only.borrow(), vec![1, 2, 3]);
+        assert_eq!(*mutating.borrow(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,56 @@
+//! Extension method for `Stream` to broadcast records to all workers.
+
+use Data;
+use dataflow::{Stream, Scope};
+use dataflow::operators::{Map, Exchange};
+
+/// Extension trait for `Stream`.
+pub trait Broadcast<S: Scope, D: Data> {
+    /// Delivers a copy of each record to every worker in the scope.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Broadcast, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .broadcast()
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn broadcast(&self) -> Stream<S, D>;
+}
+
+impl<S: Scope, D: Data> Broadcast<S, D> for Stream<S, D> {
+    fn broadcast(&self) -> Stream<S, D> {
+        let peers = self.scope().peers();
+        self.flat_map(move |datum| (0..peers).map(move |target| (target as u64, datum.clone())))
+            .exchange(|&(target, _)| target)
+            .map(|(_, datum)| datum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use ToStream;
+    use dataflow::operators::{Broadcast, Inspect};
+
+    #[test]
+    fn broadcast_delivers_every_record_to_every_worker() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen2 = seen.clone();
+
+        // With a single worker in this harness, `0..peers` tags exactly one copy of each record
+        // for delivery, so broadcasting should reproduce the input stream untouched.
+        ::example(|scope| {
+            (0 .. 5).to_stream(scope)
+                    .broadcast()
+                    .inspect(move |&x| seen2.borrow_mut().push(x));
+        });
+
+        assert_eq!(*seen.borrow(), vec![0, 1, 2, 3, 4]);
+    }
+}
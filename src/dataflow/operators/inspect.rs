@@ -0,0 +1,91 @@
+//! Extension trait for observing a stream's contents without taking ownership of them.
+
+use Data;
+use dataflow::{Stream, Scope};
+use dataflow::channels::pushers::InspectPusher;
+
+/// Extension trait for `Stream`.
+pub trait Inspect<S: Scope, D: Data> {
+    /// Runs a supplied closure on each observed data element.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn inspect<F: FnMut(&D)+'static>(&self, func: F) -> Stream<S, D>;
+    /// Runs a supplied closure on each observed data element, along with its timestamp.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .inspect_time(|t, x| println!("seen at: {:?}\t{:?}", t, x));
+    /// });
+    /// ```
+    fn inspect_time<F: FnMut(&S::Timestamp, &D)+'static>(&self, func: F) -> Stream<S, D>;
+    /// Runs a supplied closure on each observed batch of data, along with its timestamp.
+    ///
+    /// This method is meant mostly for internal use.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .inspect_batch(|t, xs| println!("seen at: {:?}\t{:?} records", t, xs.len()));
+    /// });
+    /// ```
+    fn inspect_batch<F: FnMut(&S::Timestamp, &[D])+'static>(&self, func: F) -> Stream<S, D>;
+}
+
+impl<S: Scope, D: Data> Inspect<S, D> for Stream<S, D> {
+    fn inspect<F: FnMut(&D)+'static>(&self, mut func: F) -> Stream<S, D> {
+        self.inspect_batch(move |_, data| {
+            for datum in data.iter() {
+                func(datum);
+            }
+        })
+    }
+    fn inspect_time<F: FnMut(&S::Timestamp, &D)+'static>(&self, mut func: F) -> Stream<S, D> {
+        self.inspect_batch(move |time, data| {
+            for datum in data.iter() {
+                func(time, datum);
+            }
+        })
+    }
+    fn inspect_batch<F: FnMut(&S::Timestamp, &[D])+'static>(&self, mut func: F) -> Stream<S, D> {
+        self.add_ref_pusher(InspectPusher::new(move |time: &S::Timestamp, data: &_| func(time, data)));
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use ToStream;
+    use dataflow::operators::Inspect;
+
+    #[test]
+    fn inspect_batch_sees_every_record_exactly_once() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen2 = seen.clone();
+
+        // `inspect_batch` registers through `add_ref_pusher`; it should observe every record in the stream, in order, without taking ownership of the batch.
+        ::example(|scope| {
+            (0 .. 10).to_stream(scope)
+                     .inspect_batch(move |_, data| seen2.borrow_mut().extend_from_slice(data));
+        });
+
+        assert_eq!(*seen.borrow(), (0 .. 10).collect::<Vec<_>>());
+    }
+}
@@ -1,10 +1,13 @@
 //! Extension methods for `Stream` based on record-by-record transformation.
 
+use std::collections::VecDeque;
+use std::rc::Rc;
+
 use Data;
-use dataflow::{Stream, Scope};
+use dataflow::{Stream, Scope, Capability};
 use dataflow::channels::pact::Pipeline;
 use dataflow::channels::Content;
-use dataflow::channels::pushers::{MapPusher, Tee};
+use dataflow::channels::pushers::{MapPusher, FilterPusher, Tee, into_owned};
 use dataflow::operators::generic::unary::Unary;
 
 /// Extension trait for `Stream`.
@@ -61,6 +64,38 @@ pub trait Map<S: Scope, D: Data> {
     /// });
     /// ```
     fn flat_map<I: IntoIterator, L: Fn(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data;
+    /// Retains only the elements of the stream for which `logic` evaluates to true.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .filter(|x| *x % 2 == 0)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn filter<L: Fn(&D)->bool+'static>(&self, logic: L) -> Stream<S, D>;
+    /// Consumes each element of the stream and yields the result, dropping those for which
+    /// `logic` evaluates to `None`.
+    ///
+    /// #Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .filter_map(|x| if x % 2 == 0 { Some(x + 1) } else { None })
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn filter_map<D2: Data, L: Fn(D)->Option<D2>+'static>(&self, logic: L) -> Stream<S, D2>;
+    /// Retains the elements of each batch for which `logic` evaluates to true, compacting the
+    /// batch in place rather than collecting a fresh one.
+    ///
+    /// This method is meant mostly for internal use.
+    fn retain<L: Fn(&D)->bool+'static>(&self, logic: L) -> Stream<S, D>;
     /// Consumes each message sent down the stream and yields a new, transformed message at the same time.
     ///
     /// This method is meant mostly for internal use.
@@ -130,26 +165,83 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
             data
         })
     }
-    // TODO : This would be more robust if it captured an iterator and then pulled an appropriate
-    // TODO : number of elements from the iterator. This would allow iterators that produce many
-    // TODO : records without taking arbitrarily long and arbitrarily much memory.
     fn flat_map<I: IntoIterator, L: Fn(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data {
-        self.unary_stream(Pipeline, "FlatMap", move |input, output| {
+        // Iterators produced by `logic` can be enormous (or infinite), so rather than draining
+        // one to completion per activation, we park any iterator that outlives its fuel and pick
+        // it back up on a later activation. This bounds both the work and the output of a single
+        // activation to `Content::default_length()` records, no matter how large an input's
+        // iterator turns out to be.
+        let mut pending: VecDeque<(Capability<S::Timestamp>, I::IntoIter)> = VecDeque::new();
+        self.unary_notify(Pipeline, "FlatMap", Vec::new(), move |input, output, notificator| {
+
             input.for_each(|time, data| {
-                output.session(&time).give_iterator(data.drain(..).flat_map(|x| logic(x).into_iter()));
+                for datum in data.drain(..) {
+                    pending.push_back((time.clone(), logic(datum).into_iter()));
+                }
             });
+
+            // A `notify_at` is only retired once it is delivered here; we don't care which time
+            // fired, only that we get scheduled again, so there's nothing to do with it.
+            notificator.for_each(|_, _, _| { });
+
+            // Round-robin one element at a time off the front of the queue until the fuel runs
+            // out or every pending iterator is exhausted. Re-queuing a still-live iterator at the
+            // back (rather than draining it before moving on) is what lets the fuel budget carry
+            // over to the *same* iterator again when it's the only one pending.
+            let mut fuel = Content::<I::Item>::default_length();
+            while fuel > 0 {
+                match pending.pop_front() {
+                    Some((cap, mut iter)) => {
+                        if let Some(item) = iter.next() {
+                            output.session(&cap).give(item);
+                            fuel -= 1;
+                            pending.push_back((cap, iter));
+                        }
+                        // else: iterator was exhausted; drop it rather than re-queuing.
+                    }
+                    None => break,
+                }
+            }
+
+            // As long as any iterator is still in flight, ask to be scheduled again so that it
+            // eventually drains even without further input.
+            for &(ref cap, _) in pending.iter() {
+                notificator.notify_at(cap.clone());
+            }
         })
     }
-    // fn filter_map<D2: Data, L: Fn(D)->Option<D2>+'static>(&self, logic: L) -> Stream<S, D2> {
-    //     self.unary_stream(Pipeline, "FilterMap", move |input, output| {
-    //         while let Some((time, data)) = input.next() {
-    //             output.session(time).give_iterator(data.drain(..).filter_map(|x| logic(x)));
-    //         }
-    //     })
-    // }
+    fn filter<L: Fn(&D)->bool+'static>(&self, logic: L) -> Stream<S, D> {
+        self.retain(logic)
+    }
+    fn filter_map<D2: Data, L: Fn(D)->Option<D2>+'static>(&self, logic: L) -> Stream<S, D2> {
+        self.map_batch(move |mut data| {
+            let mut mapped: Vec<_> = data.replace_with(Vec::new())
+                                         .into_iter()
+                                         .filter_map(&logic)
+                                         .collect();
+            Content::from_typed(&mut mapped)
+        })
+    }
+    fn retain<L: Fn(&D)->bool+'static>(&self, logic: L) -> Stream<S, D> {
+        let (targets, registrar) = Tee::<S::Timestamp,D>::new();
+        self.add_pusher(FilterPusher::new(logic, targets));
+
+        Stream::new(
+            *self.name(),
+            registrar,
+            self.scope()
+        )
+    }
     fn map_batch<D2: Data, L: Fn(Content<D>)->Content<D2>+'static>(&self, logic: L) -> Stream<S, D2> {
         let (targets, registrar) = Tee::<S::Timestamp,D2>::new();
-        self.add_pusher(MapPusher::new(move |(time, data)| (time, logic(data)), targets));
+        // `Tee` hands every output a shared, `Rc`-backed batch; since `logic` always takes the
+        // batch by value (to consume it or update it in place), take ownership outright if we
+        // happen to be the sole owner, falling back to a clone only when the batch is still
+        // shared with sibling outputs.
+        self.add_pusher(MapPusher::new(move |(time, data): (S::Timestamp, Rc<Content<D>>)| {
+            let owned = into_owned(data);
+            (time, logic(owned))
+        }, targets));
 
         Stream::new(
             *self.name(),
@@ -170,3 +262,29 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use ToStream;
+    use dataflow::operators::{Map, Inspect};
+
+    #[test]
+    fn flat_map_drains_a_large_iterator_across_several_activations() {
+        let seen = Rc::new(RefCell::new(0));
+        let seen2 = seen.clone();
+
+        // The iterator produced by `logic` here is far larger than `Content::default_length()`,
+        // so `flat_map` has to park and resume it across several activations; this checks that
+        // every one of its records still arrives.
+        ::example(|scope| {
+            (0 .. 1).to_stream(scope)
+                    .flat_map(|_| 0 .. 10_000)
+                    .inspect(move |_| *seen2.borrow_mut() += 1);
+        });
+
+        assert_eq!(*seen.borrow(), 10_000);
+    }
+}